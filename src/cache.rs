@@ -0,0 +1,166 @@
+//! On-disk cache for the raw archive CSVs downloaded by [`crate::divi::Api`].
+//!
+//! Raw bytes are kept (compressed) so the originally published data stays
+//! around for auditing, given the copyright notice on the DIVI register
+//! data. Alongside the bytes, the `ETag`/`Last-Modified` headers the server
+//! returned are recorded so the next sync can send conditional requests and
+//! skip re-downloading unchanged files, re-parsing the cached copy instead.
+//!
+//! The compression backend is chosen by cargo feature, mirroring how
+//! optional transfer encodings are feature-gated in reqwest: `gzip` is the
+//! default, `brotli` is opt-in.
+
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    fs::{create_dir_all, OpenOptions},
+    hash::{Hash, Hasher},
+    io::{BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+};
+
+use color_eyre::eyre::Error;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct CacheEntry {
+    etag: Option<String>,
+    last_modified: Option<String>,
+    path: PathBuf,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+struct CacheIndex {
+    entries: HashMap<Url, CacheEntry>,
+}
+
+/// Content-addressed, compressed store for raw archive CSVs, with a
+/// `cache.json` index recording the conditional-request headers for each
+/// cached URL.
+#[derive(Debug)]
+pub struct Cache {
+    path: PathBuf,
+    index: CacheIndex,
+}
+
+impl Cache {
+    pub fn new<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref().to_owned();
+
+        let raw_dir = path.join("raw");
+        if !raw_dir.exists() {
+            create_dir_all(&raw_dir)?;
+        }
+
+        let index_path = path.join("cache.json");
+        let index = if !index_path.exists() {
+            CacheIndex::default()
+        } else {
+            let file = OpenOptions::new().read(true).open(index_path)?;
+            serde_json::from_reader(BufReader::new(file))?
+        };
+
+        Ok(Self { path, index })
+    }
+
+    fn raw_path(&self, raw: &[u8]) -> PathBuf {
+        let mut hasher = DefaultHasher::new();
+        raw.hash(&mut hasher);
+        self.path.join("raw").join(format!("{:016x}{}", hasher.finish(), raw_extension()))
+    }
+
+    pub fn etag(&self, url: &Url) -> Option<&str> {
+        self.index.entries.get(url).and_then(|entry| entry.etag.as_deref())
+    }
+
+    pub fn last_modified(&self, url: &Url) -> Option<&str> {
+        self.index.entries.get(url).and_then(|entry| entry.last_modified.as_deref())
+    }
+
+    /// Compresses and stores `raw` under a content-addressed path, recording
+    /// the conditional-request headers the server returned alongside it.
+    pub fn put(&mut self, url: &Url, raw: &[u8], etag: Option<String>, last_modified: Option<String>) -> Result<(), Error> {
+        let path = self.raw_path(raw);
+        let file = OpenOptions::new().write(true).create(true).truncate(true).open(&path)?;
+        compress(BufWriter::new(file), raw)?;
+
+        self.index.entries.insert(url.clone(), CacheEntry { etag, last_modified, path });
+        self.save()
+    }
+
+    /// Loads and decompresses the raw bytes previously stored for `url`, if
+    /// it has been cached before.
+    pub fn get(&self, url: &Url) -> Result<Option<Vec<u8>>, Error> {
+        let entry = match self.index.entries.get(url) {
+            Some(entry) => entry,
+            None => return Ok(None),
+        };
+
+        let file = OpenOptions::new().read(true).open(&entry.path)?;
+        Ok(Some(decompress(BufReader::new(file))?))
+    }
+
+    fn save(&self) -> Result<(), Error> {
+        let index_path = self.path.join("cache.json");
+        let file = OpenOptions::new().write(true).create(true).truncate(true).open(index_path)?;
+        serde_json::to_writer_pretty(BufWriter::new(file), &self.index)?;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "brotli")]
+fn raw_extension() -> &'static str {
+    ".csv.br"
+}
+
+#[cfg(all(feature = "gzip", not(feature = "brotli")))]
+fn raw_extension() -> &'static str {
+    ".csv.gz"
+}
+
+#[cfg(not(any(feature = "gzip", feature = "brotli")))]
+fn raw_extension() -> &'static str {
+    ".csv"
+}
+
+#[cfg(feature = "brotli")]
+fn compress<W: Write>(mut writer: W, raw: &[u8]) -> Result<(), Error> {
+    let mut encoder = brotli::CompressorWriter::new(&mut writer, 4096, 9, 22);
+    encoder.write_all(raw)?;
+    Ok(())
+}
+
+#[cfg(all(feature = "gzip", not(feature = "brotli")))]
+fn compress<W: Write>(writer: W, raw: &[u8]) -> Result<(), Error> {
+    let mut encoder = flate2::write::GzEncoder::new(writer, flate2::Compression::default());
+    encoder.write_all(raw)?;
+    encoder.finish()?;
+    Ok(())
+}
+
+#[cfg(not(any(feature = "gzip", feature = "brotli")))]
+fn compress<W: Write>(mut writer: W, raw: &[u8]) -> Result<(), Error> {
+    writer.write_all(raw)?;
+    Ok(())
+}
+
+#[cfg(feature = "brotli")]
+fn decompress<R: Read>(mut reader: R) -> Result<Vec<u8>, Error> {
+    let mut buf = Vec::new();
+    brotli::Decompressor::new(&mut reader, 4096).read_to_end(&mut buf)?;
+    Ok(buf)
+}
+
+#[cfg(all(feature = "gzip", not(feature = "brotli")))]
+fn decompress<R: Read>(reader: R) -> Result<Vec<u8>, Error> {
+    let mut buf = Vec::new();
+    flate2::read::GzDecoder::new(reader).read_to_end(&mut buf)?;
+    Ok(buf)
+}
+
+#[cfg(not(any(feature = "gzip", feature = "brotli")))]
+fn decompress<R: Read>(mut reader: R) -> Result<Vec<u8>, Error> {
+    let mut buf = Vec::new();
+    reader.read_to_end(&mut buf)?;
+    Ok(buf)
+}
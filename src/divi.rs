@@ -1,4 +1,5 @@
 use std::{
+    collections::HashMap,
     io::Cursor,
     pin::Pin,
     task::{Context, Poll},
@@ -47,6 +48,11 @@ pub enum Error {
 
     #[error("Could not determine timestamp for dataset")]
     MissingTimestamp,
+
+    /// A single CSV row (0-based, header excluded) failed to parse, with the
+    /// underlying error that caused it.
+    #[error("Row {index} invalid: {source}")]
+    Row { index: usize, source: Box<Error> },
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -156,22 +162,27 @@ struct RawRow {
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Row {
     // TODO: Drop state? It's contained in the Gemeindeschluessel (AGS) anyway.
-    state: u8,
-    ags: String,
+    pub(crate) state: u8,
+    pub(crate) ags: String,
     #[serde(skip_serializing_if = "Option::is_none")]
-    num_report_areas: Option<usize>,
+    pub(crate) num_report_areas: Option<usize>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    cases_current: Option<usize>,
+    pub(crate) cases_current: Option<usize>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    cases_ventilated: Option<usize>,
-    num_locations: usize,
-    beds_available: usize,
-    beds_occupied: usize,
-    timestamp: NaiveDateTime,
+    pub(crate) cases_ventilated: Option<usize>,
+    pub(crate) num_locations: usize,
+    /// For archived rows, a real bed count. For rows folded from the live
+    /// report by `DataSet::from_current`, the sum over the community's
+    /// hospitals of per-hospital status tallies, each on a 0..=3 scale (see
+    /// that function's docs) — not comparable in magnitude to archived rows.
+    pub(crate) beds_available: usize,
+    /// Same caveat as `beds_available` for live-derived rows.
+    pub(crate) beds_occupied: usize,
+    pub(crate) timestamp: NaiveDateTime,
     #[serde(skip_serializing_if = "Option::is_none")]
-    beds_occupied_adults: Option<usize>,
+    pub(crate) beds_occupied_adults: Option<usize>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    beds_available_adults: Option<usize>,
+    pub(crate) beds_available_adults: Option<usize>,
 }
 
 fn parse_decimal_to_int(s: &str) -> Result<usize, Error> {
@@ -235,8 +246,103 @@ impl DataSet {
 
         Ok(Self { date, source_url, rows })
     }
+
+    /// Folds a live [`CurrentResponse`] into the same `Row`/`DataSet` schema
+    /// used for archived data, so a live snapshot can be stored alongside
+    /// archived datasets and queried with the same `state`/`ags`/
+    /// `num_locations`/`timestamp` fields.
+    ///
+    /// `Entry`s are grouped by the `community_key` (Gemeindeschlüssel) of
+    /// their `hospital_location`, summing `num_locations` and the bed-status
+    /// tallies derived from the per-hospital bed-status estimates (see
+    /// [`beds_from_estimates`]), and using the most recent `last_report_time`
+    /// in the group as the timestamp.
+    ///
+    /// Note that `beds_available`/`beds_occupied` on the resulting rows are
+    /// *not* real bed counts and are not on the same scale as archived rows'
+    /// — see [`beds_from_estimates`].
+    pub fn from_current(current: CurrentResponse, source_url: Url) -> Result<Self, Error> {
+        let mut groups: HashMap<String, CommunityAggregate> = HashMap::new();
+
+        for entry in &current.data {
+            let ags = entry.hospital_location.community_key.clone();
+            let (beds_available, beds_occupied) = beds_from_estimates(entry);
+
+            let aggregate = groups.entry(ags).or_insert_with(|| CommunityAggregate {
+                num_locations: 0,
+                beds_available: 0,
+                beds_occupied: 0,
+                last_report_time: entry.last_report_time,
+            });
+
+            aggregate.num_locations += 1;
+            aggregate.beds_available += beds_available;
+            aggregate.beds_occupied += beds_occupied;
+            aggregate.last_report_time = aggregate.last_report_time.max(entry.last_report_time);
+        }
+
+        let rows = groups
+            .into_iter()
+            .map(|(ags, aggregate)| {
+                let state = ags.get(0..2).ok_or(Error::InvalidRow)?.parse()?;
+
+                Ok(Row {
+                    state,
+                    ags,
+                    num_report_areas: None,
+                    cases_current: None,
+                    cases_ventilated: None,
+                    num_locations: aggregate.num_locations,
+                    beds_available: aggregate.beds_available,
+                    beds_occupied: aggregate.beds_occupied,
+                    timestamp: aggregate.last_report_time.naive_utc(),
+                    beds_occupied_adults: None,
+                    beds_available_adults: None,
+                })
+            })
+            .collect::<Result<Vec<Row>, Error>>()?;
+
+        DataSet::new(source_url, rows)
+    }
 }
 
+struct CommunityAggregate {
+    num_locations: usize,
+    beds_available: usize,
+    beds_occupied: usize,
+    last_report_time: DateTime<Utc>,
+}
+
+/// Tallies a hospital's per-care-level (ECMO, high care, low care)
+/// bed-status-estimate ("Betten-Ampel") strings into an `(available,
+/// occupied)` pair, since the live report only exposes a traffic-light
+/// status per care level rather than raw bed counts like the archived CSVs.
+///
+/// This is **not** a bed count: the result is always in `0..=3` (one point
+/// per care level), regardless of how many actual beds that status
+/// represents, so it must not be compared numerically against archived
+/// rows' `beds_available`/`beds_occupied` (real counts, typically much
+/// larger). It exists only so live rows can populate those fields at all.
+fn beds_from_estimates(entry: &Entry) -> (usize, usize) {
+    [
+        &entry.max_beds_status_estimate_ecmo,
+        &entry.max_beds_status_estimate_high_care,
+        &entry.max_beds_status_estimate_low_care,
+    ]
+    .iter()
+    .fold((0, 0), |(available, occupied), status| match status.as_str() {
+        "VERFUEGBAR" => (available + 1, occupied),
+        "BEGRENZT" | "NICHT_VERFUEGBAR" => (available, occupied + 1),
+        "KEINE_ANGABE" => (available, occupied),
+        _ => {
+            tracing::warn!("Unrecognized bed-status estimate: {}", status);
+            (available, occupied)
+        }
+    })
+}
+
+pub const CURRENT_URL: &str = "https://www.intensivregister.de/api/public/intensivregister";
+
 #[derive(Debug, Default)]
 pub struct Api {
     client: Client,
@@ -244,28 +350,47 @@ pub struct Api {
 
 impl Api {
     pub async fn get_current(&self) -> Result<CurrentResponse, Error> {
-        Ok(self
-            .client
-            .get("https://www.intensivregister.de/api/public/intensivregister")
-            .send()
-            .await?
-            .json()
-            .await?)
+        Ok(self.client.get(CURRENT_URL).send().await?.json().await?)
     }
 
     pub async fn get_archived(&self, url: Url) -> Result<DataSet, Error> {
         let data = self.client.get(url.clone()).send().await?.bytes().await?;
+        parse_archived_csv(url, &data)
+    }
 
-        let mut reader = CsvReader::from_reader(Cursor::new(data));
+    /// Like [`get_archived`](Self::get_archived), but sends `If-None-Match`
+    /// / `If-Modified-Since` conditional headers (as recorded by a previous
+    /// fetch) and lets the caller skip re-downloading and re-parsing when
+    /// the server confirms the cached copy is still current.
+    ///
+    /// If `lenient` is set, a malformed row doesn't abort parsing the whole
+    /// CSV: bad rows are collected into [`ArchivedResponse::row_errors`] and
+    /// the good rows are still returned.
+    pub async fn get_archived_conditional(&self, url: Url, etag: Option<&str>, last_modified: Option<&str>, lenient: bool) -> Result<GetArchivedResult, Error> {
+        let mut request = self.client.get(url.clone());
+        if let Some(etag) = etag {
+            request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = last_modified {
+            request = request.header(reqwest::header::IF_MODIFIED_SINCE, last_modified);
+        }
 
-        let timestamp_hint = timestamp_hint_from_url(&url);
+        let response = request.send().await?;
 
-        let rows = reader
-            .deserialize::<RawRow>()
-            .map(|r| Ok(Row::from_raw(r?, &timestamp_hint)?))
-            .collect::<Result<Vec<Row>, Error>>()?;
+        if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+            return Ok(GetArchivedResult::NotModified);
+        }
 
-        Ok(DataSet::new(url, rows)?)
+        let etag = header_as_string(response.headers(), reqwest::header::ETAG);
+        let last_modified = header_as_string(response.headers(), reqwest::header::LAST_MODIFIED);
+        let raw = response.bytes().await?.to_vec();
+        let (dataset, row_errors) = if lenient {
+            parse_archived_csv_lenient(url, &raw)?
+        } else {
+            (parse_archived_csv(url, &raw)?, vec![])
+        };
+
+        Ok(GetArchivedResult::Modified(ArchivedResponse { dataset, etag, last_modified, raw, row_errors }))
     }
 
     pub fn list_archived<'a>(&'a self) -> Result<ArchiveStream<'a>, Error> {
@@ -273,6 +398,78 @@ impl Api {
     }
 }
 
+fn header_as_string(headers: &reqwest::header::HeaderMap, name: reqwest::header::HeaderName) -> Option<String> {
+    headers.get(name).and_then(|value| value.to_str().ok()).map(String::from)
+}
+
+/// An error parsing a single row of an archive CSV, with the index of the
+/// row (0-based, header excluded) it occurred at.
+#[derive(Debug)]
+pub struct RowError {
+    pub index: usize,
+    pub error: Error,
+}
+
+/// Like [`parse_archived_csv`], but doesn't abort on the first malformed
+/// row: bad rows are returned alongside the good ones instead of aborting
+/// the whole parse.
+pub(crate) fn parse_archived_csv_lenient(url: Url, data: &[u8]) -> Result<(DataSet, Vec<RowError>), Error> {
+    let mut reader = CsvReader::from_reader(Cursor::new(data));
+
+    let timestamp_hint = timestamp_hint_from_url(&url);
+
+    let mut rows = vec![];
+    let mut row_errors = vec![];
+
+    for (index, result) in reader.deserialize::<RawRow>().enumerate() {
+        match result.map_err(Error::from).and_then(|raw| Row::from_raw(raw, &timestamp_hint)) {
+            Ok(row) => rows.push(row),
+            Err(error) => row_errors.push(RowError { index, error }),
+        }
+    }
+
+    Ok((DataSet::new(url, rows)?, row_errors))
+}
+
+pub(crate) fn parse_archived_csv(url: Url, data: &[u8]) -> Result<DataSet, Error> {
+    let mut reader = CsvReader::from_reader(Cursor::new(data));
+
+    let timestamp_hint = timestamp_hint_from_url(&url);
+
+    let rows = reader
+        .deserialize::<RawRow>()
+        .enumerate()
+        .map(|(index, r)| {
+            r.map_err(Error::from)
+                .and_then(|raw| Row::from_raw(raw, &timestamp_hint))
+                .map_err(|source| Error::Row { index, source: Box::new(source) })
+        })
+        .collect::<Result<Vec<Row>, Error>>()?;
+
+    DataSet::new(url, rows)
+}
+
+/// The raw bytes and headers of a freshly downloaded archive CSV, together
+/// with the [`DataSet`] parsed from it.
+pub struct ArchivedResponse {
+    pub dataset: DataSet,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    pub raw: Vec<u8>,
+    /// Per-row errors encountered while parsing, when fetched leniently.
+    /// Always empty otherwise.
+    pub row_errors: Vec<RowError>,
+}
+
+/// Result of [`Api::get_archived_conditional`].
+pub enum GetArchivedResult {
+    /// The server confirmed (via `304 Not Modified`) that the cached copy is
+    /// still current.
+    NotModified,
+    /// The server returned new data.
+    Modified(ArchivedResponse),
+}
+
 fn timestamp_hint_from_url(url: &Url) -> Option<NaiveDateTime> {
     let regex = Regex::new(r"(\d{4})-(\d{2})-(\d{2})-(\d{2})-(\d{2})").unwrap();
 
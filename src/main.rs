@@ -50,16 +50,57 @@
 //! ``` 
 //! 
 
+mod cache;
 mod divi;
+mod query;
+mod report;
+mod scheduler;
 mod store;
 
-use std::path::PathBuf;
+use std::{
+    path::PathBuf,
+    time::{Duration, Instant},
+};
 
+use chrono::NaiveDate;
 use color_eyre::eyre::Error;
 use futures::stream::StreamExt;
 use structopt::StructOpt;
+use url::Url;
 
-use store::Store;
+use scheduler::{Scheduler, Task};
+use store::{DataStore, Store};
+
+/// Which [`DataStore`] implementation to use, selected via `sync --backend`.
+#[derive(Clone, Copy, Debug)]
+enum Backend {
+    Json,
+    Sqlite,
+}
+
+impl std::str::FromStr for Backend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(Backend::Json),
+            "sqlite" => Ok(Backend::Sqlite),
+            _ => Err(format!("Unknown backend: `{}` (expected `json` or `sqlite`)", s)),
+        }
+    }
+}
+
+impl Backend {
+    fn open(self, data_dir: &PathBuf) -> Result<Box<dyn DataStore>, Error> {
+        match self {
+            Backend::Json => Ok(Box::new(Store::new(data_dir)?)),
+            #[cfg(feature = "sqlite")]
+            Backend::Sqlite => Ok(Box::new(store::sqlite::SqliteStore::new(data_dir)?)),
+            #[cfg(not(feature = "sqlite"))]
+            Backend::Sqlite => Err(color_eyre::eyre::eyre!("The `sqlite` backend requires the `sqlite` cargo feature.")),
+        }
+    }
+}
 
 #[derive(Clone, Debug, StructOpt)]
 enum Args {
@@ -83,9 +124,163 @@ enum Args {
         /// Ignore already downloaded files and sync everything.
         #[structopt(long, short = "A")]
         resync_all: bool,
+
+        /// Which storage backend to use.
+        #[structopt(long, default_value = "json")]
+        backend: Backend,
+
+        /// Don't abort the whole sync when a single row of an archive CSV
+        /// fails to parse; skip it and keep going.
+        #[structopt(long)]
+        lenient: bool,
+
+        /// Write a structured, machine-readable report of this run's
+        /// outcome (downloaded/not-modified/skipped/parse-error/http-error
+        /// per URL) to this path.
+        #[structopt(long)]
+        report: Option<PathBuf>,
+    },
+    /// Fetch the daily report for today, normalize it into the same schema
+    /// as archived datasets, and store it.
+    Today {
+        /// Directory in which the normalized DIVI data is stored.
+        #[structopt(short = "d", long = "data", default_value = "./data")]
+        data_dir: PathBuf,
+    },
+
+    /// Run a filter-expression query against the datasets stored in a data
+    /// directory.
+    ///
+    /// Example:
+    ///
+    /// ```plain
+    /// divi-tool query -d data --from 2021-01-01 --to 2021-03-01 \
+    ///     "state = 5 AND beds_available > 100 AND cases_ventilated >= 10"
+    /// ```
+    Query {
+        /// Directory in which the normalized DIVI data is stored.
+        #[structopt(short = "d", long = "data", default_value = "./data")]
+        data_dir: PathBuf,
+
+        /// Which storage backend to query; must match the one `sync` used
+        /// to populate `data_dir`.
+        #[structopt(long, default_value = "json")]
+        backend: Backend,
+
+        /// Start date (inclusive) of the range of datasets to query.
+        #[structopt(long)]
+        from: NaiveDate,
+
+        /// End date (inclusive) of the range of datasets to query.
+        #[structopt(long)]
+        to: NaiveDate,
+
+        /// Print matching rows as a table instead of JSON.
+        #[structopt(long)]
+        table: bool,
+
+        /// The filter expression, e.g. `state = 5 AND beds_available > 100`.
+        expr: String,
+    },
+
+    /// Continuously poll the live and archived endpoints and write new
+    /// datasets into the data directory as they appear, instead of having
+    /// to run `sync` from a cron job.
+    Watch {
+        /// Directory in which the normalized DIVI data is stored.
+        #[structopt(short = "d", long = "data", default_value = "./data")]
+        data_dir: PathBuf,
+
+        /// Interval (in seconds) at which the live report is polled.
+        #[structopt(long, default_value = "300")]
+        current_interval: u64,
+
+        /// Interval (in seconds) at which the archive is polled for new
+        /// files.
+        #[structopt(long, default_value = "3600")]
+        archive_interval: u64,
     },
-    /// Fetch the daily report for today.
-    Today,
+}
+
+/// Downloads and stores the archive CSV at `url`, using the cache's
+/// conditional-request headers to avoid re-downloading unchanged files (the
+/// cached copy is re-parsed and stored instead). Shared between `sync` and
+/// `watch`.
+///
+/// If `report` is given, records this URL's outcome into it. On an error,
+/// the outcome is still recorded before the error is propagated, as
+/// [`report::Outcome::HttpError`] for a [`divi::Error::Http`] and
+/// [`report::Outcome::ParseError`] for anything else (a malformed row, bad
+/// CSV header, empty dataset, ...). Errors writing to the store are
+/// propagated without a report entry.
+async fn sync_one(api: &divi::Api, store: &mut dyn DataStore, url: Url, lenient: bool, mut report: Option<&mut report::Report>) -> Result<(), Error> {
+    let (etag, last_modified) = store.cache_headers(&url);
+
+    tracing::info!("Downloading {}", url);
+
+    let fetch_result = match api.get_archived_conditional(url.clone(), etag.as_deref(), last_modified.as_deref(), lenient).await {
+        Ok(fetch_result) => fetch_result,
+        Err(error) => {
+            if let Some(report) = report.as_deref_mut() {
+                let outcome = match &error {
+                    divi::Error::Http(_) => report::Outcome::HttpError { error: error.to_string() },
+                    divi::Error::Row { index, source } => {
+                        report::Outcome::ParseError { error: source.to_string(), row_index: Some(*index) }
+                    }
+                    error => report::Outcome::ParseError { error: error.to_string(), row_index: None },
+                };
+                report.push(url, outcome);
+            }
+            return Err(error.into());
+        }
+    };
+
+    match fetch_result {
+        divi::GetArchivedResult::NotModified => {
+            tracing::info!("Not modified, using cached copy: {}", url);
+            if let Some(raw) = store.cached_raw(&url)? {
+                let parsed = if lenient {
+                    divi::parse_archived_csv_lenient(url.clone(), &raw)
+                } else {
+                    divi::parse_archived_csv(url.clone(), &raw).map(|dataset| (dataset, vec![]))
+                };
+                let (dataset, row_errors) = match parsed {
+                    Ok(parsed) => parsed,
+                    Err(error) => {
+                        if let Some(report) = report.as_deref_mut() {
+                            let outcome = match &error {
+                                divi::Error::Row { index, source } => {
+                                    report::Outcome::ParseError { error: source.to_string(), row_index: Some(*index) }
+                                }
+                                error => report::Outcome::ParseError { error: error.to_string(), row_index: None },
+                            };
+                            report.push(url, outcome);
+                        }
+                        return Err(error.into());
+                    }
+                };
+                store.put_dataset(&dataset)?;
+
+                if let Some(report) = report.as_deref_mut() {
+                    report.push_row_errors(&url, &row_errors);
+                    report.push(url, report::Outcome::NotModified);
+                }
+            } else if let Some(report) = report.as_deref_mut() {
+                report.push(url, report::Outcome::NoCachedCopy);
+            }
+        }
+        divi::GetArchivedResult::Modified(response) => {
+            store.put_dataset(&response.dataset)?;
+            store.cache_raw(&url, &response.raw, response.etag, response.last_modified)?;
+
+            if let Some(report) = report.as_deref_mut() {
+                report.push_row_errors(&url, &response.row_errors);
+                report.push(url, report::Outcome::Downloaded);
+            }
+        }
+    }
+
+    Ok(())
 }
 
 impl Args {
@@ -95,36 +290,150 @@ impl Args {
                 data_dir,
                 check_all,
                 resync_all,
+                backend,
+                lenient,
+                report,
             } => {
-                let mut store = Store::new(&data_dir)?;
+                let mut store = backend.open(&data_dir)?;
+                let mut sync_report = report.as_ref().map(|_| report::Report::new());
 
                 let api = divi::Api::default();
                 let mut archived = api.list_archived()?;
 
-                while let Some(result) = archived.next().await {
-                    let url = result?;
+                let result = async {
+                    while let Some(result) = archived.next().await {
+                        let url = result?;
 
-                    if !resync_all && store.contains_dataset_source_url(&url) {
-                        if check_all {
-                            tracing::info!("Skipping {}", url);
+                        if !resync_all && store.contains_dataset_source_url(&url) {
+                            if let Some(sync_report) = sync_report.as_mut() {
+                                sync_report.push(url.clone(), report::Outcome::SkippedKnown);
+                            }
+
+                            if check_all {
+                                tracing::info!("Skipping {}", url);
+                            } else {
+                                tracing::info!("Stopping, already known: {}", url);
+                                break;
+                            }
                         } else {
-                            tracing::info!("Stopping, already known: {}", url);
-                            break;
+                            sync_one(&api, store.as_mut(), url, lenient, sync_report.as_mut()).await?;
                         }
-                    } else {
-                        tracing::info!("Downloading {}", url);
-                        let dataset = api.get_archived(url).await?;
-                        store.put_dataset(&dataset)?;
                     }
+
+                    Ok::<(), Error>(())
+                }
+                .await;
+
+                if let (Some(path), Some(sync_report)) = (&report, &sync_report) {
+                    sync_report.write(path)?;
                 }
+
+                result?;
             }
-            Args::Today => {
+            Args::Today { data_dir } => {
+                let mut store = Store::new(&data_dir)?;
                 let api = divi::Api::default();
                 let today = api.get_current().await?;
 
-                //for entry in today {}
+                let dataset = divi::DataSet::from_current(today, divi::CURRENT_URL.parse()?)?;
+                store.put_dataset(&dataset)?;
+
+                println!("{:#?}", dataset);
+            }
+            Args::Query { data_dir, backend, from, to, table, expr } => {
+                let store = backend.open(&data_dir)?;
+                let expr = query::parse(&expr)?;
+
+                for row in &store.query_rows(from, to)? {
+                    if query::evaluate(&expr, row) {
+                        if table {
+                            println!(
+                                "{}\t{}\t{}\t{}/{}",
+                                row.timestamp, row.ags, row.state, row.beds_occupied, row.beds_available
+                            );
+                        } else {
+                            println!("{}", serde_json::to_string(row)?);
+                        }
+                    }
+                }
+            }
+            Args::Watch { data_dir, current_interval, archive_interval } => {
+                let mut store = Store::new(&data_dir)?;
+                let api = divi::Api::default();
+
+                let current_interval = Duration::from_secs(current_interval);
+                let archive_interval = Duration::from_secs(archive_interval);
+
+                let mut scheduler = Scheduler::new();
+                let now = Instant::now();
+                scheduler.schedule(Task::PollCurrent, now);
+                scheduler.schedule(Task::PollArchive, now + Duration::from_nanos(1));
+
+                loop {
+                    let deadline = match scheduler.peek() {
+                        Some(deadline) => deadline,
+                        None => break,
+                    };
+
+                    let now = Instant::now();
+                    if deadline > now {
+                        tracing::trace!("next_run in {:?}", deadline - now);
+
+                        tokio::select! {
+                            _ = tokio::time::sleep_until(deadline.into()) => {}
+                            _ = tokio::signal::ctrl_c() => {
+                                tracing::info!("Received SIGINT, shutting down");
+                                break;
+                            }
+                        }
+                    }
+
+                    let task = scheduler.pop().expect("peeked deadline disappeared");
 
-                println!("{:#?}", today);
+                    match task {
+                        Task::PollCurrent => {
+                            tracing::info!("Polling current report");
+
+                            let result: Result<(), Error> = async {
+                                let today = api.get_current().await?;
+                                let dataset = divi::DataSet::from_current(today, divi::CURRENT_URL.parse()?)?;
+                                store.put_dataset(&dataset)?;
+                                Ok(())
+                            }
+                            .await;
+
+                            if let Err(error) = result {
+                                tracing::error!("Polling current report failed, will retry next interval: {}", error);
+                            }
+
+                            scheduler.schedule(Task::PollCurrent, Instant::now() + current_interval);
+                        }
+                        Task::PollArchive => {
+                            tracing::info!("Polling archive");
+
+                            let result: Result<(), Error> = async {
+                                let mut archived = api.list_archived()?;
+
+                                while let Some(result) = archived.next().await {
+                                    let url = result?;
+                                    if store.contains_dataset_source_url(&url) {
+                                        break;
+                                    }
+                                    sync_one(&api, &mut store, url, false, None).await?;
+                                }
+
+                                Ok(())
+                            }
+                            .await;
+
+                            if let Err(error) = result {
+                                tracing::error!("Polling archive failed, will retry next interval: {}", error);
+                            }
+
+                            scheduler.schedule(Task::PollArchive, Instant::now() + archive_interval);
+                        }
+                    }
+                }
             }
         }
 
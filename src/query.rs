@@ -0,0 +1,378 @@
+//! A small filter-expression DSL for querying [`Row`]s, inspired by the
+//! filter grammars of search engines: `field op value` comparisons combined
+//! with `AND`/`OR`/`NOT` and parentheses, e.g.
+//!
+//! ```text
+//! state = 5 AND beds_available > 100 AND cases_ventilated >= 10
+//! ```
+
+use thiserror::Error;
+
+use crate::divi::Row;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("Unexpected end of input")]
+    UnexpectedEof,
+
+    #[error("Unexpected token: {0}")]
+    UnexpectedToken(String),
+
+    #[error("Unknown field: {0}")]
+    UnknownField(String),
+
+    #[error("Invalid number: {0}")]
+    InvalidNumber(String),
+
+    #[error("Unterminated string literal")]
+    UnterminatedString,
+
+    #[error("Expected closing parenthesis")]
+    MissingClosingParen,
+
+    #[error("`{0}` is a string field; quote the value instead of writing it as a number")]
+    NumericLiteralForStringField(String),
+}
+
+/// A `Row` field that can appear on the left-hand side of a comparison.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Field {
+    State,
+    Ags,
+    BedsAvailable,
+    BedsOccupied,
+    CasesCurrent,
+    CasesVentilated,
+    NumLocations,
+    Timestamp,
+}
+
+impl Field {
+    fn from_ident(ident: &str) -> Result<Self, Error> {
+        Ok(match ident {
+            "state" => Field::State,
+            "ags" => Field::Ags,
+            "beds_available" => Field::BedsAvailable,
+            "beds_occupied" => Field::BedsOccupied,
+            "cases_current" => Field::CasesCurrent,
+            "cases_ventilated" => Field::CasesVentilated,
+            "num_locations" => Field::NumLocations,
+            "timestamp" => Field::Timestamp,
+            _ => return Err(Error::UnknownField(ident.to_owned())),
+        })
+    }
+
+    /// Whether this field is compared numerically (as opposed to as a
+    /// string).
+    fn is_numeric(&self) -> bool {
+        !matches!(self, Field::Ags | Field::Timestamp)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Number(f64),
+    String(String),
+}
+
+#[derive(Clone, Debug)]
+pub enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Cmp { field: Field, op: Op, value: Value },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Ident(String),
+    String(String),
+    Number(f64),
+    Op(Op),
+    And,
+    Or,
+    Not,
+    LParen,
+    RParen,
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, Error> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = vec![];
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+
+        if c.is_whitespace() {
+            i += 1;
+        } else if c == '(' {
+            tokens.push(Token::LParen);
+            i += 1;
+        } else if c == ')' {
+            tokens.push(Token::RParen);
+            i += 1;
+        } else if c == '"' {
+            let mut s = String::new();
+            i += 1;
+            loop {
+                match chars.get(i) {
+                    Some('"') => {
+                        i += 1;
+                        break;
+                    }
+                    Some(c) => {
+                        s.push(*c);
+                        i += 1;
+                    }
+                    None => return Err(Error::UnterminatedString),
+                }
+            }
+            tokens.push(Token::String(s));
+        } else if c == '=' {
+            tokens.push(Token::Op(Op::Eq));
+            i += 1;
+        } else if c == '!' && chars.get(i + 1) == Some(&'=') {
+            tokens.push(Token::Op(Op::Ne));
+            i += 2;
+        } else if c == '<' {
+            if chars.get(i + 1) == Some(&'=') {
+                tokens.push(Token::Op(Op::Le));
+                i += 2;
+            } else {
+                tokens.push(Token::Op(Op::Lt));
+                i += 1;
+            }
+        } else if c == '>' {
+            if chars.get(i + 1) == Some(&'=') {
+                tokens.push(Token::Op(Op::Ge));
+                i += 2;
+            } else {
+                tokens.push(Token::Op(Op::Gt));
+                i += 1;
+            }
+        } else if c.is_ascii_digit() || (c == '-' && chars.get(i + 1).map_or(false, |c| c.is_ascii_digit())) {
+            let start = i;
+            i += 1;
+            while chars.get(i).map_or(false, |c| c.is_ascii_digit() || *c == '.') {
+                i += 1;
+            }
+            let s: String = chars[start..i].iter().collect();
+            tokens.push(Token::Number(s.parse().map_err(|_| Error::InvalidNumber(s))?));
+        } else if c.is_alphanumeric() || c == '_' {
+            let start = i;
+            while chars.get(i).map_or(false, |c| c.is_alphanumeric() || *c == '_' || *c == '-') {
+                i += 1;
+            }
+            let ident: String = chars[start..i].iter().collect();
+            tokens.push(match ident.to_ascii_uppercase().as_str() {
+                "AND" => Token::And,
+                "OR" => Token::Or,
+                "NOT" => Token::Not,
+                _ => Token::Ident(ident),
+            });
+        } else {
+            return Err(Error::UnexpectedToken(c.to_string()));
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    // expr := or_expr
+    fn parse_expr(&mut self) -> Result<Expr, Error> {
+        self.parse_or()
+    }
+
+    // or_expr := and_expr (OR and_expr)*
+    fn parse_or(&mut self) -> Result<Expr, Error> {
+        let mut lhs = self.parse_and()?;
+
+        while let Some(Token::Or) = self.peek() {
+            self.next();
+            let rhs = self.parse_and()?;
+            lhs = Expr::Or(Box::new(lhs), Box::new(rhs));
+        }
+
+        Ok(lhs)
+    }
+
+    // and_expr := not_expr (AND not_expr)*
+    fn parse_and(&mut self) -> Result<Expr, Error> {
+        let mut lhs = self.parse_not()?;
+
+        while let Some(Token::And) = self.peek() {
+            self.next();
+            let rhs = self.parse_not()?;
+            lhs = Expr::And(Box::new(lhs), Box::new(rhs));
+        }
+
+        Ok(lhs)
+    }
+
+    // not_expr := NOT not_expr | atom
+    fn parse_not(&mut self) -> Result<Expr, Error> {
+        if let Some(Token::Not) = self.peek() {
+            self.next();
+            let inner = self.parse_not()?;
+            Ok(Expr::Not(Box::new(inner)))
+        } else {
+            self.parse_atom()
+        }
+    }
+
+    // atom := "(" expr ")" | cmp
+    fn parse_atom(&mut self) -> Result<Expr, Error> {
+        match self.next().ok_or(Error::UnexpectedEof)? {
+            Token::LParen => {
+                let expr = self.parse_expr()?;
+                match self.next() {
+                    Some(Token::RParen) => Ok(expr),
+                    _ => Err(Error::MissingClosingParen),
+                }
+            }
+            Token::Ident(ident) => self.parse_cmp(ident),
+            token => Err(Error::UnexpectedToken(format!("{:?}", token))),
+        }
+    }
+
+    // cmp := IDENT OP (STRING | NUMBER | IDENT)
+    fn parse_cmp(&mut self, ident: String) -> Result<Expr, Error> {
+        let field = Field::from_ident(&ident)?;
+
+        let op = match self.next().ok_or(Error::UnexpectedEof)? {
+            Token::Op(op) => op,
+            token => return Err(Error::UnexpectedToken(format!("{:?}", token))),
+        };
+
+        let value = match self.next().ok_or(Error::UnexpectedEof)? {
+            Token::Number(n) => {
+                if !field.is_numeric() {
+                    return Err(Error::NumericLiteralForStringField(ident));
+                }
+                Value::Number(n)
+            }
+            Token::String(s) => Value::String(s),
+            Token::Ident(s) => Value::String(s),
+            token => return Err(Error::UnexpectedToken(format!("{:?}", token))),
+        };
+
+        Ok(Expr::Cmp { field, op, value })
+    }
+}
+
+/// Parses a filter expression into an [`Expr`] AST.
+pub fn parse(input: &str) -> Result<Expr, Error> {
+    let tokens = tokenize(input)?;
+    let mut parser = Parser { tokens, pos: 0 };
+    let expr = parser.parse_expr()?;
+
+    if let Some(token) = parser.peek() {
+        return Err(Error::UnexpectedToken(format!("{:?}", token)));
+    }
+
+    Ok(expr)
+}
+
+fn compare_numbers(lhs: f64, op: Op, rhs: f64) -> bool {
+    match op {
+        Op::Eq => lhs == rhs,
+        Op::Ne => lhs != rhs,
+        Op::Lt => lhs < rhs,
+        Op::Le => lhs <= rhs,
+        Op::Gt => lhs > rhs,
+        Op::Ge => lhs >= rhs,
+    }
+}
+
+fn compare_strings(lhs: &str, op: Op, rhs: &str) -> bool {
+    match op {
+        Op::Eq => lhs == rhs,
+        Op::Ne => lhs != rhs,
+        Op::Lt => lhs < rhs,
+        Op::Le => lhs <= rhs,
+        Op::Gt => lhs > rhs,
+        Op::Ge => lhs >= rhs,
+    }
+}
+
+fn field_as_number(field: Field, row: &Row) -> Option<f64> {
+    Some(match field {
+        Field::State => row.state as f64,
+        Field::BedsAvailable => row.beds_available as f64,
+        Field::BedsOccupied => row.beds_occupied as f64,
+        Field::CasesCurrent => row.cases_current? as f64,
+        Field::CasesVentilated => row.cases_ventilated? as f64,
+        Field::NumLocations => row.num_locations as f64,
+        Field::Ags | Field::Timestamp => return None,
+    })
+}
+
+fn field_as_string(field: Field, row: &Row) -> String {
+    match field {
+        Field::Ags => row.ags.clone(),
+        Field::Timestamp => row.timestamp.to_string(),
+        _ => unreachable!("numeric field passed to field_as_string"),
+    }
+}
+
+/// Evaluates a parsed filter expression against a single `Row`.
+///
+/// `Option` fields (e.g. `cases_current`) evaluate to `false` for numeric
+/// comparisons when they're `None`.
+pub fn evaluate(expr: &Expr, row: &Row) -> bool {
+    match expr {
+        Expr::And(lhs, rhs) => evaluate(lhs, row) && evaluate(rhs, row),
+        Expr::Or(lhs, rhs) => evaluate(lhs, row) || evaluate(rhs, row),
+        Expr::Not(inner) => !evaluate(inner, row),
+        Expr::Cmp { field, op, value } => {
+            if field.is_numeric() {
+                let lhs = match field_as_number(*field, row) {
+                    Some(lhs) => lhs,
+                    None => return false,
+                };
+                let rhs = match value {
+                    Value::Number(n) => *n,
+                    Value::String(s) => match s.parse() {
+                        Ok(n) => n,
+                        Err(_) => return false,
+                    },
+                };
+                compare_numbers(lhs, *op, rhs)
+            } else {
+                let lhs = field_as_string(*field, row);
+                let rhs = match value {
+                    Value::String(s) => s.clone(),
+                    Value::Number(n) => n.to_string(),
+                };
+                compare_strings(&lhs, *op, &rhs)
+            }
+        }
+    }
+}
@@ -0,0 +1,98 @@
+//! Structured, machine-readable reports for a `sync` run: for every URL the
+//! archive yields, records whether it was downloaded, confirmed not modified
+//! since the cached copy, skipped because it was already known, or failed
+//! with an HTTP or parse error.
+//!
+//! Serialized as JSON by default; pass the `report-yaml` cargo feature to
+//! serialize as YAML instead, mirroring how the cache's compression backend
+//! is chosen by cargo feature.
+
+use std::{
+    fs::OpenOptions,
+    io::BufWriter,
+    path::Path,
+};
+
+use color_eyre::eyre::Error;
+use serde::Serialize;
+use url::Url;
+
+use crate::divi::RowError;
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum Outcome {
+    Downloaded,
+    SkippedKnown,
+    ParseError {
+        error: String,
+        /// The row that failed to parse, if the error could be attributed to
+        /// one (e.g. `None` for a malformed CSV header or an empty dataset).
+        row_index: Option<usize>,
+    },
+    HttpError {
+        error: String,
+    },
+    /// The server reported the cached copy is still current (HTTP 304), and
+    /// the cached copy was re-parsed and (re-)stored; no bytes were
+    /// downloaded.
+    NotModified,
+    /// The server reported the cached copy is still current (HTTP 304), but
+    /// no raw bytes for this URL were found in the store to re-parse.
+    NoCachedCopy,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct UrlReport {
+    pub url: Url,
+    pub outcome: Outcome,
+}
+
+/// A sync run's report: an outcome entry per URL the `ArchiveStream`
+/// yielded, plus, for leniently parsed files, one `ParseError` entry per
+/// skipped row of that URL.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct Report {
+    pub urls: Vec<UrlReport>,
+}
+
+impl Report {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, url: Url, outcome: Outcome) {
+        self.urls.push(UrlReport { url, outcome });
+    }
+
+    /// Records one [`Outcome::ParseError`] per row error, all for the same
+    /// `url`.
+    pub fn push_row_errors(&mut self, url: &Url, row_errors: &[RowError]) {
+        for row_error in row_errors {
+            self.push(
+                url.clone(),
+                Outcome::ParseError {
+                    error: row_error.error.to_string(),
+                    row_index: Some(row_error.index),
+                },
+            );
+        }
+    }
+
+    pub fn write(&self, path: &Path) -> Result<(), Error> {
+        let file = OpenOptions::new().write(true).create(true).truncate(true).open(path)?;
+        write_report(BufWriter::new(file), self)
+    }
+}
+
+#[cfg(feature = "report-yaml")]
+fn write_report<W: std::io::Write>(writer: W, report: &Report) -> Result<(), Error> {
+    serde_yaml::to_writer(writer, report)?;
+    Ok(())
+}
+
+#[cfg(not(feature = "report-yaml"))]
+fn write_report<W: std::io::Write>(writer: W, report: &Report) -> Result<(), Error> {
+    serde_json::to_writer_pretty(writer, report)?;
+    Ok(())
+}
@@ -0,0 +1,53 @@
+//! A small buffered, time-ordered run queue used by the `watch` subcommand.
+//!
+//! Pending tasks are kept in a `BTreeSet<(Instant, Task)>` keyed by their
+//! deadline (and then the task itself, to keep the key unique when two
+//! tasks share a deadline), so the earliest one is always peeked/popped
+//! first. Scheduling a task that is already buffered is a no-op (coalesced)
+//! rather than queueing a second, redundant run.
+
+use std::{
+    collections::{BTreeSet, HashSet},
+    time::Instant,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Task {
+    PollCurrent,
+    PollArchive,
+}
+
+#[derive(Debug, Default)]
+pub struct Scheduler {
+    queue: BTreeSet<(Instant, Task)>,
+    pending: HashSet<Task>,
+}
+
+impl Scheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Buffers `task` to run at `deadline`, unless it's already buffered, in
+    /// which case the existing entry is left as-is.
+    pub fn schedule(&mut self, task: Task, deadline: Instant) {
+        if !self.pending.insert(task) {
+            return;
+        }
+
+        self.queue.insert((deadline, task));
+    }
+
+    /// Returns the deadline of the next task to run, without removing it.
+    pub fn peek(&self) -> Option<Instant> {
+        self.queue.iter().next().map(|(deadline, _)| *deadline)
+    }
+
+    /// Removes and returns the earliest buffered task.
+    pub fn pop(&mut self) -> Option<Task> {
+        let entry = self.queue.iter().next().copied()?;
+        self.queue.remove(&entry);
+        self.pending.remove(&entry.1);
+        Some(entry.1)
+    }
+}
@@ -0,0 +1,165 @@
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+
+use std::{
+    collections::HashSet,
+    fs::{create_dir_all, read_dir, OpenOptions},
+    io::{BufReader, BufWriter},
+    path::{Path, PathBuf},
+};
+
+use chrono::NaiveDate;
+use color_eyre::eyre::Error;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::{
+    cache::Cache,
+    divi::{DataSet, Row},
+};
+
+/// A backend that can hold normalized DIVI [`DataSet`]s and the set of
+/// archive URLs already synced.
+///
+/// The JSON-file [`Store`] is the zero-dependency default; a SQLite-backed
+/// implementation is available behind the `sqlite` cargo feature (see
+/// [`sqlite::SqliteStore`]) for efficient time-series queries across the
+/// whole archive, which the per-day JSON files can't do without loading
+/// every file.
+pub trait DataStore {
+    fn get_dataset(&self, date: &NaiveDate) -> Result<DataSet, Error>;
+
+    fn put_dataset(&mut self, dataset: &DataSet) -> Result<(), Error>;
+
+    fn contains_dataset_source_url(&self, source_url: &Url) -> bool;
+
+    /// Returns all `Row`s whose timestamp falls into `[from, to]`.
+    fn query_rows(&self, from: NaiveDate, to: NaiveDate) -> Result<Vec<Row>, Error>;
+
+    /// The `ETag` and `Last-Modified` headers recorded for `url`'s previous
+    /// download, if any, for use as conditional-request headers.
+    fn cache_headers(&self, url: &Url) -> (Option<String>, Option<String>);
+
+    /// The raw (decompressed) bytes cached for `url`'s previous download, if
+    /// any.
+    fn cached_raw(&self, url: &Url) -> Result<Option<Vec<u8>>, Error>;
+
+    /// Compresses and stores `raw` for `url`, along with the conditional-
+    /// request headers the server returned for it.
+    fn cache_raw(&mut self, url: &Url, raw: &[u8], etag: Option<String>, last_modified: Option<String>) -> Result<(), Error>;
+}
+
+#[derive(Debug)]
+pub struct Store {
+    path: PathBuf,
+    info: Info,
+    cache: Cache,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+struct Info {
+    urls_synced: HashSet<Url>,
+}
+
+impl Store {
+    pub fn new<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+
+        if !path.exists() {
+            create_dir_all(&path)?;
+        }
+
+        let info_path = path.join("info.json");
+        let info = if !info_path.exists() {
+            Info::default()
+        } else {
+            let opt = OpenOptions::new().read(true).open(info_path)?;
+            serde_json::from_reader(BufReader::new(opt))?
+        };
+
+        let cache = Cache::new(path)?;
+
+        Ok(Self { path: path.to_owned(), info, cache })
+    }
+
+    fn rows_path(&self, date: &NaiveDate) -> PathBuf {
+        self.path.join(format!("{}.json", date.format("%Y-%m-%d")))
+    }
+
+    pub fn get_dataset(&self, date: &NaiveDate) -> Result<DataSet, Error> {
+        let file = OpenOptions::new().read(true).open(self.rows_path(date))?;
+
+        Ok(serde_json::from_reader(BufReader::new(file))?)
+    }
+
+    pub fn put_dataset(&mut self, dataset: &DataSet) -> Result<(), Error> {
+        let file = OpenOptions::new().write(true).create(true).truncate(true).open(self.rows_path(&dataset.date))?;
+
+        serde_json::to_writer_pretty(BufWriter::new(file), dataset)?;
+        self.info.urls_synced.insert(dataset.source_url.clone());
+
+        self.save_info()?;
+
+        Ok(())
+    }
+
+    pub fn contains_dataset_source_url(&self, source_url: &Url) -> bool {
+        self.info.urls_synced.contains(source_url)
+    }
+
+    /// Lists the [`DataSet`]s whose date falls into `[from, to]`, in
+    /// ascending order, by scanning the store directory for `*.json` files
+    /// named after their date.
+    pub fn iter_datasets_in_range(&self, from: NaiveDate, to: NaiveDate) -> Result<Vec<DataSet>, Error> {
+        let mut dates = read_dir(&self.path)?
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let path = entry.path();
+                let stem = path.file_stem()?.to_str()?;
+                NaiveDate::parse_from_str(stem, "%Y-%m-%d").ok()
+            })
+            .filter(|date| *date >= from && *date <= to)
+            .collect::<Vec<_>>();
+
+        dates.sort();
+
+        dates.into_iter().map(|date| self.get_dataset(&date)).collect()
+    }
+
+    pub fn save_info(&self) -> Result<(), Error> {
+        let info_path = self.path.join("info.json");
+        let file = OpenOptions::new().write(true).create(true).truncate(true).open(info_path)?;
+        serde_json::to_writer_pretty(BufWriter::new(file), &self.info)?;
+        Ok(())
+    }
+}
+
+impl DataStore for Store {
+    fn get_dataset(&self, date: &NaiveDate) -> Result<DataSet, Error> {
+        Store::get_dataset(self, date)
+    }
+
+    fn put_dataset(&mut self, dataset: &DataSet) -> Result<(), Error> {
+        Store::put_dataset(self, dataset)
+    }
+
+    fn contains_dataset_source_url(&self, source_url: &Url) -> bool {
+        Store::contains_dataset_source_url(self, source_url)
+    }
+
+    fn query_rows(&self, from: NaiveDate, to: NaiveDate) -> Result<Vec<Row>, Error> {
+        Ok(self.iter_datasets_in_range(from, to)?.into_iter().flat_map(|dataset| dataset.rows).collect())
+    }
+
+    fn cache_headers(&self, url: &Url) -> (Option<String>, Option<String>) {
+        (self.cache.etag(url).map(String::from), self.cache.last_modified(url).map(String::from))
+    }
+
+    fn cached_raw(&self, url: &Url) -> Result<Option<Vec<u8>>, Error> {
+        self.cache.get(url)
+    }
+
+    fn cache_raw(&mut self, url: &Url, raw: &[u8], etag: Option<String>, last_modified: Option<String>) -> Result<(), Error> {
+        self.cache.put(url, raw, etag, last_modified)
+    }
+}
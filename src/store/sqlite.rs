@@ -0,0 +1,190 @@
+//! SQLite-backed [`DataStore`] implementation, gated behind the `sqlite`
+//! cargo feature.
+//!
+//! Rows are kept in a single table normalized on `(ags, timestamp)`, with
+//! indexes on `state` and `timestamp`, so range/field queries across the
+//! whole archive don't require loading every per-day JSON file. The set of
+//! already-synced URLs lives in its own table, replacing `info.json`.
+
+use std::{
+    fs::create_dir_all,
+    path::{Path, PathBuf},
+};
+
+use chrono::{NaiveDate, NaiveDateTime};
+use color_eyre::eyre::Error;
+use rusqlite::{params, Connection, OptionalExtension};
+use url::Url;
+
+use super::DataStore;
+use crate::{
+    cache::Cache,
+    divi::{DataSet, Row},
+};
+
+pub struct SqliteStore {
+    conn: Connection,
+    cache: Cache,
+}
+
+impl SqliteStore {
+    pub fn new<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+
+        if !path.exists() {
+            create_dir_all(path)?;
+        }
+
+        let conn = Connection::open(path.join("store.sqlite3"))?;
+        conn.execute_batch(
+            r#"
+            CREATE TABLE IF NOT EXISTS rows (
+                ags TEXT NOT NULL,
+                timestamp TEXT NOT NULL,
+                state INTEGER NOT NULL,
+                num_report_areas INTEGER,
+                cases_current INTEGER,
+                cases_ventilated INTEGER,
+                num_locations INTEGER NOT NULL,
+                beds_available INTEGER NOT NULL,
+                beds_occupied INTEGER NOT NULL,
+                beds_occupied_adults INTEGER,
+                beds_available_adults INTEGER,
+                PRIMARY KEY (ags, timestamp)
+            );
+            CREATE INDEX IF NOT EXISTS rows_state ON rows (state);
+            CREATE INDEX IF NOT EXISTS rows_timestamp ON rows (timestamp);
+
+            CREATE TABLE IF NOT EXISTS datasets (
+                date TEXT PRIMARY KEY,
+                source_url TEXT NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS synced_urls (
+                url TEXT PRIMARY KEY
+            );
+            "#,
+        )?;
+
+        let cache = Cache::new(path)?;
+
+        Ok(Self { conn, cache })
+    }
+}
+
+const ROW_COLUMNS: &str =
+    "ags, timestamp, state, num_report_areas, cases_current, cases_ventilated, num_locations, beds_available, beds_occupied, beds_occupied_adults, beds_available_adults";
+
+fn row_from_sql(sql_row: &rusqlite::Row) -> rusqlite::Result<Row> {
+    let timestamp = timestamp_from_sql(&sql_row.get::<_, String>(1)?)
+        .map_err(|error| rusqlite::Error::FromSqlConversionFailure(1, rusqlite::types::Type::Text, Box::new(error)))?;
+
+    Ok(Row {
+        ags: sql_row.get(0)?,
+        timestamp,
+        state: sql_row.get(2)?,
+        num_report_areas: sql_row.get(3)?,
+        cases_current: sql_row.get(4)?,
+        cases_ventilated: sql_row.get(5)?,
+        num_locations: sql_row.get(6)?,
+        beds_available: sql_row.get(7)?,
+        beds_occupied: sql_row.get(8)?,
+        beds_occupied_adults: sql_row.get(9)?,
+        beds_available_adults: sql_row.get(10)?,
+    })
+}
+
+fn timestamp_to_sql(timestamp: &NaiveDateTime) -> String {
+    timestamp.format("%Y-%m-%dT%H:%M:%S").to_string()
+}
+
+/// Parses a timestamp stored in the `rows` table, as written by
+/// [`timestamp_to_sql`]. Returns an error (rather than panicking) on a
+/// malformed value, e.g. one written by an incompatible earlier version of
+/// the store.
+fn timestamp_from_sql(s: &str) -> Result<NaiveDateTime, chrono::ParseError> {
+    NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S")
+}
+
+impl DataStore for SqliteStore {
+    fn get_dataset(&self, date: &NaiveDate) -> Result<DataSet, Error> {
+        let source_url: String =
+            self.conn.query_row("SELECT source_url FROM datasets WHERE date = ?1", params![date.to_string()], |row| row.get(0))?;
+
+        let day_prefix = format!("{}%", date.format("%Y-%m-%dT"));
+        let mut stmt = self.conn.prepare(&format!("SELECT {} FROM rows WHERE timestamp LIKE ?1", ROW_COLUMNS))?;
+        let rows = stmt.query_map(params![day_prefix], row_from_sql)?.collect::<Result<Vec<_>, _>>()?;
+
+        Ok(DataSet {
+            date: *date,
+            source_url: source_url.parse()?,
+            rows,
+        })
+    }
+
+    fn put_dataset(&mut self, dataset: &DataSet) -> Result<(), Error> {
+        let tx = self.conn.transaction()?;
+
+        for row in &dataset.rows {
+            tx.execute(
+                &format!(
+                    "INSERT OR REPLACE INTO rows ({}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+                    ROW_COLUMNS
+                ),
+                params![
+                    row.ags,
+                    timestamp_to_sql(&row.timestamp),
+                    row.state,
+                    row.num_report_areas,
+                    row.cases_current,
+                    row.cases_ventilated,
+                    row.num_locations,
+                    row.beds_available,
+                    row.beds_occupied,
+                    row.beds_occupied_adults,
+                    row.beds_available_adults,
+                ],
+            )?;
+        }
+
+        tx.execute(
+            "INSERT OR REPLACE INTO datasets (date, source_url) VALUES (?1, ?2)",
+            params![dataset.date.to_string(), dataset.source_url.as_str()],
+        )?;
+        tx.execute("INSERT OR IGNORE INTO synced_urls (url) VALUES (?1)", params![dataset.source_url.as_str()])?;
+
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    fn contains_dataset_source_url(&self, source_url: &Url) -> bool {
+        self.conn
+            .query_row("SELECT 1 FROM synced_urls WHERE url = ?1", params![source_url.as_str()], |_| Ok(()))
+            .optional()
+            .unwrap_or(None)
+            .is_some()
+    }
+
+    fn query_rows(&self, from: NaiveDate, to: NaiveDate) -> Result<Vec<Row>, Error> {
+        let from = from.format("%Y-%m-%dT00:00:00").to_string();
+        let to = to.format("%Y-%m-%dT23:59:59").to_string();
+
+        let mut stmt = self.conn.prepare(&format!("SELECT {} FROM rows WHERE timestamp BETWEEN ?1 AND ?2 ORDER BY timestamp", ROW_COLUMNS))?;
+        let rows = stmt.query_map(params![from, to], row_from_sql)?.collect::<Result<Vec<_>, _>>()?;
+
+        Ok(rows)
+    }
+
+    fn cache_headers(&self, url: &Url) -> (Option<String>, Option<String>) {
+        (self.cache.etag(url).map(String::from), self.cache.last_modified(url).map(String::from))
+    }
+
+    fn cached_raw(&self, url: &Url) -> Result<Option<Vec<u8>>, Error> {
+        self.cache.get(url)
+    }
+
+    fn cache_raw(&mut self, url: &Url, raw: &[u8], etag: Option<String>, last_modified: Option<String>) -> Result<(), Error> {
+        self.cache.put(url, raw, etag, last_modified)
+    }
+}